@@ -0,0 +1,81 @@
+//! Walks a directory of Python source (typically the stdlib `Lib/`
+//! directory), compiles every module it finds, and writes out a single
+//! `bincode`-serialized blob mapping module name to `CodeObject`. The
+//! result is meant to be embedded and fed into `vm.frozen` so startup
+//! never has to reparse the standard library.
+
+use rustpython_compiler::compile;
+use rustpython_vm::bytecode::CodeObject;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+fn module_name_for(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap();
+    let rel = rel.with_extension("");
+    let mut parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.last().map(String::as_str) == Some("__init__") {
+        parts.pop();
+    }
+    parts.join(".")
+}
+
+fn collect_py_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_py_files(&path, out)?;
+        } else if path.extension().map_or(false, |ext| ext == "py") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let stdlib_dir = match args.next() {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            eprintln!("usage: freeze <stdlib-dir> <output-file>");
+            process::exit(1);
+        }
+    };
+    let output_path = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => {
+            eprintln!("usage: freeze <stdlib-dir> <output-file>");
+            process::exit(1);
+        }
+    };
+
+    let mut py_files = Vec::new();
+    collect_py_files(&stdlib_dir, &mut py_files).expect("failed to walk stdlib directory");
+
+    let mut modules: HashMap<String, Vec<u8>> = HashMap::new();
+    for path in py_files {
+        let name = module_name_for(&stdlib_dir, &path);
+        let source = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        let code_obj: CodeObject =
+            compile::compile(&source, &compile::Mode::Exec, path.to_string_lossy().into_owned())
+                .unwrap_or_else(|e| panic!("failed to compile {}: {}", path.display(), e));
+        let bytes = bincode::serialize(&code_obj)
+            .unwrap_or_else(|e| panic!("failed to serialize {}: {}", name, e));
+        modules.insert(name, bytes);
+    }
+
+    let blob = bincode::serialize(&modules).expect("failed to serialize frozen module map");
+    fs::write(&output_path, blob).expect("failed to write frozen module blob");
+    println!(
+        "froze {} modules into {}",
+        modules.len(),
+        output_path.display()
+    );
+}
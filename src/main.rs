@@ -7,8 +7,9 @@ extern crate rustyline;
 
 use clap::{App, Arg};
 use rustpython_compiler::{compile, error::CompileError, error::CompileErrorType};
-use rustpython_parser::error::ParseErrorType;
+use rustpython_parser::{ast, error::ParseErrorType, parser};
 use rustpython_vm::{
+    bytecode::CodeObject,
     frame::Scope,
     import,
     obj::objstr,
@@ -18,13 +19,15 @@ use rustpython_vm::{
 };
 
 use rustyline::{error::ReadlineError, Editor};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::{Duration, Instant};
 
 fn main() {
     #[cfg(feature = "flame-it")]
     let main_guard = flame::start_guard("RustPython main");
-    env_logger::init();
     let app = App::new("RustPython")
         .version(crate_version!())
         .author(crate_authors!())
@@ -54,6 +57,34 @@ fn main() {
                 .takes_value(true)
                 .help("run library module as script"),
         )
+        .arg(
+            Arg::with_name("dont_write_bytecode")
+                .short("B")
+                .help("don't write .rpyc files on import; also set by $PYTHONDONTWRITEBYTECODE"),
+        )
+        .arg(
+            Arg::with_name("freeze")
+                .long("freeze")
+                .takes_value(true)
+                .help("compute the import closure of the given script and write it out as a single self-contained module bundle"),
+        )
+        .arg(
+            Arg::with_name("bundle_output")
+                .long("bundle-output")
+                .takes_value(true)
+                .help("output path for --freeze (default: <script>.rpybundle)"),
+        )
+        .arg(
+            Arg::with_name("run_bundle")
+                .long("run-bundle")
+                .takes_value(true)
+                .help("execute a bundle produced by --freeze, with no filesystem import lookups at runtime"),
+        )
+        .arg(
+            Arg::with_name("time")
+                .long("time")
+                .help("print how long parsing, compiling, and running took, without generating a full flamegraph"),
+        )
         .arg(Arg::from_usage("[pyargs] 'args for python'").multiple(true));
     #[cfg(feature = "flame-it")]
     let app = app
@@ -71,30 +102,65 @@ fn main() {
         );
     let matches = app.get_matches();
 
+    // `-v` sets the log level (each extra `-v` one step more verbose) for
+    // `debug!` output; the import machinery's per-module timing line (in a
+    // different crate) piggybacks on that via `log_enabled!` rather than
+    // its own ad hoc flag, so `RUST_LOG` still works as an override.
+    let verbosity = matches.occurrences_of("v");
+    let mut log_builder = env_logger::Builder::from_default_env();
+    if std::env::var_os("RUST_LOG").is_none() {
+        let level = match verbosity {
+            0 => log::LevelFilter::Warn,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        };
+        log_builder.filter_level(level);
+    }
+    log_builder.init();
+
     let opt_level = matches.occurrences_of("optimize");
     let optimize = opt_level > 0;
     debug!("Optimize: {}", optimize);
+
+    if matches.is_present("dont_write_bytecode") {
+        std::env::set_var("PYTHONDONTWRITEBYTECODE", "1");
+    }
+
+    let time_it = matches.is_present("time");
+
     // Construct vm:
     let vm = VirtualMachine::new(optimize);
 
     let res = import::init_importlib(&vm, true);
     handle_exception(&vm, res);
 
-    // Figure out if a -c option was given:
-    let result = if let Some(command) = matches.value_of("c") {
-        run_command(&vm, command.to_string())
-    } else if let Some(module) = matches.value_of("m") {
-        run_module(&vm, module)
+    if let Some(entry) = matches.value_of("freeze") {
+        let output = matches
+            .value_of("bundle_output")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}.rpybundle", entry));
+        freeze_bundle(&vm, entry, &output);
+    } else if let Some(bundle_path) = matches.value_of("run_bundle") {
+        let result = run_bundle(&vm, bundle_path);
+        handle_exception(&vm, result);
     } else {
-        // Figure out if a script was passed:
-        match matches.value_of("script") {
-            None => run_shell(&vm),
-            Some(filename) => run_script(&vm, filename),
-        }
-    };
+        // Figure out if a -c option was given:
+        let result = if let Some(command) = matches.value_of("c") {
+            run_command(&vm, command.to_string(), time_it)
+        } else if let Some(module) = matches.value_of("m") {
+            run_module(&vm, module)
+        } else {
+            // Figure out if a script was passed:
+            match matches.value_of("script") {
+                None => run_shell(&vm),
+                Some(filename) => run_script(&vm, filename, time_it),
+            }
+        };
 
-    // See if any exception leaked out:
-    handle_exception(&vm, result);
+        // See if any exception leaked out:
+        handle_exception(&vm, result);
+    }
 
     #[cfg(feature = "flame-it")]
     {
@@ -150,14 +216,277 @@ fn write_profile(matches: clap::ArgMatches) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
-fn _run_string(vm: &VirtualMachine, source: &str, source_path: String) -> PyResult {
+/// A single-file distribution of a RustPython program: the entry script's
+/// own compiled code plus every module `collect_imports` finds it
+/// transitively importing, each
+/// pre-compiled to a `CodeObject` so `run-bundle` never has to touch the
+/// filesystem or the parser/compiler at runtime.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Bundle {
+    entry: Vec<u8>,
+    modules: HashMap<String, Vec<u8>>,
+}
+
+/// Resolve a (possibly relative) `from` import to an absolute module name,
+/// the same way `importlib._bootstrap._resolve_name` does: `level` dots
+/// strip `level - 1` trailing components off `package` (the importing
+/// module's own package, i.e. its name if it's a package itself, else its
+/// name minus the last component) before appending `module`.
+fn resolve_relative_import(package: &str, module: Option<&str>, level: usize) -> Option<String> {
+    if level == 0 {
+        return module.map(str::to_string);
+    }
+    if package.is_empty() {
+        // A relative import outside of any package can't be resolved ahead
+        // of time; skip it rather than guessing.
+        return None;
+    }
+    let bits: Vec<&str> = package.rsplitn(level, '.').collect();
+    if bits.len() < level {
+        return None;
+    }
+    let base = *bits.last().unwrap();
+    Some(match module {
+        Some(module) if !module.is_empty() => format!("{}.{}", base, module),
+        _ => base.to_string(),
+    })
+}
+
+/// A module's `__package__`: itself if it's a package (`__init__.py`),
+/// otherwise everything before its last dotted component.
+fn package_of(module_name: &str, is_package: bool) -> String {
+    if is_package {
+        return module_name.to_string();
+    }
+    match module_name.rfind('.') {
+        Some(idx) => module_name[..idx].to_string(),
+        None => String::new(),
+    }
+}
+
+fn is_package_location(located: &import::LocatedModule) -> bool {
+    match located {
+        import::LocatedModule::File(path) => {
+            path.file_name().map_or(false, |f| f == "__init__.py")
+        }
+        import::LocatedModule::Zip { member, .. } => {
+            member == "__init__.py" || member.ends_with("/__init__.py")
+        }
+    }
+}
+
+/// Walk a module's AST collecting the absolute names of everything it
+/// imports (resolving relative `from` imports against `package`), so
+/// `freeze_bundle` can follow the import closure ahead of time, via
+/// `find_source`. `from pkg import name` records `pkg` only: a submodule
+/// imported that way is not followed.
+fn collect_imports(source: &str, package: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(program) = parser::parse_program(source) {
+        collect_imports_in_suite(&program.statements, package, &mut names);
+    }
+    names
+}
+
+fn collect_imports_in_suite(suite: &[ast::Statement], package: &str, names: &mut Vec<String>) {
+    for statement in suite {
+        match &statement.node {
+            ast::StatementType::Import { names: imports } => {
+                names.extend(imports.iter().map(|i| i.symbol.clone()));
+            }
+            ast::StatementType::ImportFrom {
+                module,
+                level,
+                ..
+            } => {
+                if let Some(resolved) =
+                    resolve_relative_import(package, module.as_deref(), *level)
+                {
+                    names.push(resolved);
+                }
+            }
+            ast::StatementType::If { body, orelse, .. }
+            | ast::StatementType::While { body, orelse, .. }
+            | ast::StatementType::For { body, orelse, .. } => {
+                collect_imports_in_suite(body, package, names);
+                if let Some(orelse) = orelse {
+                    collect_imports_in_suite(orelse, package, names);
+                }
+            }
+            ast::StatementType::Try {
+                body,
+                handlers,
+                orelse,
+                finalbody,
+                ..
+            } => {
+                collect_imports_in_suite(body, package, names);
+                for handler in handlers {
+                    collect_imports_in_suite(&handler.body, package, names);
+                }
+                if let Some(orelse) = orelse {
+                    collect_imports_in_suite(orelse, package, names);
+                }
+                if let Some(finalbody) = finalbody {
+                    collect_imports_in_suite(finalbody, package, names);
+                }
+            }
+            ast::StatementType::FunctionDef { body, .. }
+            | ast::StatementType::ClassDef { body, .. }
+            | ast::StatementType::With { body, .. } => {
+                collect_imports_in_suite(body, package, names);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `--freeze <entry>`: resolve the entry script's whole import closure
+/// ahead of time (reusing `find_source`, the same resolver a live import
+/// would use) and write every reachable module out as one bundle file.
+fn freeze_bundle(vm: &VirtualMachine, entry_path: &str, output_path: &str) {
+    let entry_path_buf = PathBuf::from(entry_path);
+    let entry_dir = entry_path_buf
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+
+    let entry_source = util::read_file(&entry_path_buf).unwrap_or_else(|e| {
+        error!("Failed reading entry script '{}': {:?}", entry_path, e.kind());
+        process::exit(1);
+    });
+    let entry_code = vm
+        .compile(&entry_source, &compile::Mode::Exec, entry_path.to_string())
+        .unwrap_or_else(|err| {
+            let exc = vm.new_syntax_error(&err);
+            print_exception(vm, &exc);
+            process::exit(1);
+        });
+    let entry_bytes = bincode::serialize(&entry_code).expect("failed to serialize entry module");
+
+    let mut modules: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    // A top-level entry script has no package of its own, so relative
+    // imports directly inside it can't be resolved (same as running it
+    // for real).
+    let mut queue: VecDeque<String> = collect_imports(&entry_source, "").into();
+
+    while let Some(name) = queue.pop_front() {
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+        // Importing `a.b` runs `a` first, so parent packages belong to the
+        // closure too.
+        if let Some(idx) = name.rfind('.') {
+            queue.push_back(name[..idx].to_string());
+        }
+        let located = match import::find_source(vm, entry_dir.clone(), &name) {
+            Ok(located) => located,
+            // Not found on disk: a builtin/frozen module, already available
+            // wherever the bundle ends up running.
+            Err(_) => continue,
+        };
+        let package = package_of(&name, is_package_location(&located));
+        let (display_path, source) = match import::read_located_source(&located) {
+            Ok(pair) => pair,
+            Err(_) => continue,
+        };
+        let code_obj = match vm.compile(&source, &compile::Mode::Exec, display_path) {
+            Ok(code_obj) => code_obj,
+            Err(_) => continue,
+        };
+        modules.insert(
+            name.clone(),
+            bincode::serialize(&code_obj).expect("failed to serialize module"),
+        );
+        queue.extend(collect_imports(&source, &package));
+    }
+
+    let module_count = modules.len();
+    let bundle = Bundle {
+        entry: entry_bytes,
+        modules,
+    };
+    let blob = bincode::serialize(&bundle).expect("failed to serialize bundle");
+    fs::write(output_path, blob).unwrap_or_else(|e| {
+        error!("Failed writing bundle '{}': {:?}", output_path, e.kind());
+        process::exit(1);
+    });
+    println!(
+        "froze {} modules (plus entry) into {}",
+        module_count, output_path
+    );
+}
+
+/// `--run-bundle <bundle>`: register every bundled module in the frozen
+/// registry, then run the entry module, so bundled modules are served from
+/// memory rather than looked up on the filesystem.
+fn run_bundle(vm: &VirtualMachine, bundle_path: &str) -> PyResult {
+    let bytes = fs::read(bundle_path).unwrap_or_else(|e| {
+        error!("Failed reading bundle '{}': {:?}", bundle_path, e.kind());
+        process::exit(1);
+    });
+    let bundle: Bundle = bincode::deserialize(&bytes).unwrap_or_else(|e| {
+        error!("Corrupt bundle '{}': {}", bundle_path, e);
+        process::exit(1);
+    });
+    for (name, code) in bundle.modules {
+        import::register_frozen_module(vm, name, code);
+    }
+    let entry_code: CodeObject = bincode::deserialize(&bundle.entry).unwrap_or_else(|e| {
+        error!("Corrupt bundle entry in '{}': {}", bundle_path, e);
+        process::exit(1);
+    });
+    let attrs = vm.ctx.new_dict();
+    attrs.set_item("__file__", vm.new_str(bundle_path.to_string()), vm)?;
+    vm.run_code_obj(entry_code, Scope::with_builtins(None, attrs, vm))
+}
+
+/// Print a compact, aligned, colorized summary of how long each phase took.
+/// The lighter-weight sibling of `flame-it`: no flamegraph to generate and
+/// open, just "why is this slow" at a glance.
+fn print_phase_timings(phases: &[(&str, Duration)]) {
+    eprintln!("\x1b[1mtiming:\x1b[0m");
+    for (label, duration) in phases {
+        eprintln!(
+            "  {:<8} \x1b[33m{:>10.3}ms\x1b[0m",
+            label,
+            duration.as_secs_f64() * 1000.0
+        );
+    }
+}
+
+fn _run_string(vm: &VirtualMachine, source: &str, source_path: String, time_it: bool) -> PyResult {
+    let parse_start = Instant::now();
+    if time_it {
+        // The compiler below reparses internally; this call is purely to
+        // attribute time to parsing on its own in the report.
+        let _ = parser::parse_program(source);
+    }
+    let parse_duration = parse_start.elapsed();
+
+    let compile_start = Instant::now();
     let code_obj = vm
         .compile(source, &compile::Mode::Exec, source_path.clone())
         .map_err(|err| vm.new_syntax_error(&err))?;
+    let compile_duration = compile_start.elapsed();
     // trace!("Code object: {:?}", code_obj.borrow());
     let attrs = vm.ctx.new_dict();
     attrs.set_item("__file__", vm.new_str(source_path), vm)?;
-    vm.run_code_obj(code_obj, Scope::with_builtins(None, attrs, vm))
+
+    let run_start = Instant::now();
+    let result = vm.run_code_obj(code_obj, Scope::with_builtins(None, attrs, vm));
+    let run_duration = run_start.elapsed();
+
+    if time_it {
+        print_phase_timings(&[
+            ("parse", parse_duration),
+            ("compile", compile_duration),
+            ("execute", run_duration),
+        ]);
+    }
+
+    result
 }
 
 fn handle_exception(vm: &VirtualMachine, result: PyResult) {
@@ -167,12 +496,12 @@ fn handle_exception(vm: &VirtualMachine, result: PyResult) {
     }
 }
 
-fn run_command(vm: &VirtualMachine, mut source: String) -> PyResult {
+fn run_command(vm: &VirtualMachine, mut source: String, time_it: bool) -> PyResult {
     debug!("Running command {}", source);
 
     // This works around https://github.com/RustPython/RustPython/issues/17
     source.push('\n');
-    _run_string(vm, &source, "<stdin>".to_string())
+    _run_string(vm, &source, "<stdin>".to_string(), time_it)
 }
 
 fn run_module(vm: &VirtualMachine, module: &str) -> PyResult {
@@ -180,7 +509,7 @@ fn run_module(vm: &VirtualMachine, module: &str) -> PyResult {
     vm.import(module, &vm.ctx.new_tuple(vec![]), 0)
 }
 
-fn run_script(vm: &VirtualMachine, script_file: &str) -> PyResult {
+fn run_script(vm: &VirtualMachine, script_file: &str, time_it: bool) -> PyResult {
     debug!("Running file {}", script_file);
     // Parse an ast from it:
     let file_path = PathBuf::from(script_file);
@@ -210,7 +539,7 @@ fn run_script(vm: &VirtualMachine, script_file: &str) -> PyResult {
     vm.call_method(&sys_path, "insert", vec![vm.new_int(0), vm.new_str(dir)])?;
 
     match util::read_file(&file_path) {
-        Ok(source) => _run_string(vm, &source, file_path.to_str().unwrap().to_string()),
+        Ok(source) => _run_string(vm, &source, file_path.to_str().unwrap().to_string(), time_it),
         Err(err) => {
             error!(
                 "Failed reading file '{}': {:?}",
@@ -227,14 +556,41 @@ fn test_run_script() {
     let vm: VirtualMachine = Default::default();
 
     // test file run
-    let r = run_script(&vm, "tests/snippets/dir_main/__main__.py");
+    let r = run_script(&vm, "tests/snippets/dir_main/__main__.py", false);
     assert!(r.is_ok());
 
     // test module run
-    let r = run_script(&vm, "tests/snippets/dir_main");
+    let r = run_script(&vm, "tests/snippets/dir_main", false);
     assert!(r.is_ok());
 }
 
+#[test]
+fn test_freeze_bundle_resolves_relative_imports() {
+    let vm: VirtualMachine = Default::default();
+    let bundle_path =
+        std::env::temp_dir().join(format!("rustpython_test_bundle_{}.bin", std::process::id()));
+
+    // `relpkg_entry.py` does `import relpkg.main`, and `relpkg/main.py` in
+    // turn does `from .helper import value` -- if the relative import were
+    // dropped from the closure (the bug under review) `relpkg.helper`
+    // wouldn't make it into the bundle and running it would fail.
+    freeze_bundle(
+        &vm,
+        "tests/snippets/relpkg_entry.py",
+        bundle_path.to_str().unwrap(),
+    );
+    let result = run_bundle(&vm, bundle_path.to_str().unwrap());
+    let _ = fs::remove_file(&bundle_path);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_run_string_with_timing_smoke() {
+    let vm: VirtualMachine = Default::default();
+    let result = _run_string(&vm, "1 + 1", "<timing_test>".to_string(), true);
+    assert!(result.is_ok());
+}
+
 fn shell_exec(vm: &VirtualMachine, source: &str, scope: Scope) -> Result<(), CompileError> {
     match vm.compile(source, &compile::Mode::Single, "<stdin>".to_string()) {
         Ok(code) => {
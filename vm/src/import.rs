@@ -2,15 +2,106 @@
  * Import mechanics
  */
 
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read as IoRead;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+
+use crate::bytecode::CodeObject;
 use crate::compile;
 use crate::frame::Scope;
 use crate::obj::{objsequence, objstr};
-use crate::pyobject::{ItemProtocol, PyResult};
+use crate::pyobject::{IdProtocol, ItemProtocol, PyObjectRef, PyResult};
 use crate::util;
 use crate::vm::VirtualMachine;
 
+// Bumped whenever the on-disk cache format or the bytecode it embeds changes
+// in an incompatible way, forcing every existing `__pycache__` entry to be
+// recompiled rather than misread.
+const PYC_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct PycHeader {
+    version: u32,
+    source_size: u64,
+    source_mtime_secs: u64,
+    source_mtime_nanos: u32,
+}
+
+fn dont_write_bytecode() -> bool {
+    std::env::var_os("PYTHONDONTWRITEBYTECODE").is_some()
+}
+
+fn cache_path_for(source_path: &Path) -> Option<PathBuf> {
+    let file_name = source_path.file_name()?;
+    let dir = source_path.parent().unwrap_or_else(|| Path::new("."));
+    Some(
+        dir.join("__pycache__")
+            .join(file_name)
+            .with_extension("rpyc"),
+    )
+}
+
+fn stat_header(source_path: &Path) -> Option<PycHeader> {
+    let meta = fs::metadata(source_path).ok()?;
+    // Whole seconds alone aren't enough: two edits within the same second
+    // (common under fast CI/codegen) would otherwise hash to the same
+    // header and serve stale bytecode.
+    let mtime: Duration = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(PycHeader {
+        version: PYC_FORMAT_VERSION,
+        source_size: meta.len(),
+        source_mtime_secs: mtime.as_secs(),
+        source_mtime_nanos: mtime.subsec_nanos(),
+    })
+}
+
+fn load_cached_code(source_path: &Path) -> Option<CodeObject> {
+    let cache_path = cache_path_for(source_path)?;
+    let current = stat_header(source_path)?;
+    let bytes = fs::read(&cache_path).ok()?;
+    let (header, code): (PycHeader, CodeObject) = bincode::deserialize(&bytes).ok()?;
+    if header.version == current.version
+        && header.source_size == current.source_size
+        && header.source_mtime_secs == current.source_mtime_secs
+        && header.source_mtime_nanos == current.source_mtime_nanos
+    {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+fn store_cached_code(source_path: &Path, code: &CodeObject) {
+    if dont_write_bytecode() {
+        return;
+    }
+    let (cache_path, header) = match (cache_path_for(source_path), stat_header(source_path)) {
+        (Some(cache_path), Some(header)) => (cache_path, header),
+        _ => return,
+    };
+    let bytes = match bincode::serialize(&(header, code)) {
+        Ok(bytes) => bytes,
+        Err(_) => return,
+    };
+    // Best-effort: a read-only source directory (or any other write
+    // failure) just means we stay uncached, not a hard error.
+    if let Some(dir) = cache_path.parent() {
+        if fs::create_dir_all(dir).is_err() {
+            return;
+        }
+    }
+    let tmp_path = cache_path.with_extension("rpyc.tmp");
+    if fs::write(&tmp_path, bytes).is_err() {
+        return;
+    }
+    let _ = fs::rename(&tmp_path, &cache_path);
+}
+
 pub fn init_importlib(vm: &VirtualMachine) -> PyResult {
     let importlib = import_frozen(vm, "_frozen_importlib")?;
     let impmod = import_builtin(vm, "_imp")?;
@@ -20,22 +111,68 @@ pub fn init_importlib(vm: &VirtualMachine) -> PyResult {
         .replace(vm.get_attribute(importlib.clone(), "__import__")?);
     let install_external = vm.get_attribute(importlib.clone(), "_install_external_importers")?;
     vm.invoke(install_external, vec![])?;
+    ensure_meta_path_defaults(vm)?;
+    Ok(vm.get_none())
+}
+
+// Make sure sys.meta_path/path_hooks/path_importer_cache exist before the
+// first import, so Python code can register finders ahead of time.
+fn ensure_meta_path_defaults(vm: &VirtualMachine) -> PyResult {
+    if vm
+        .get_attribute(vm.sys_module.clone(), "meta_path")
+        .is_err()
+    {
+        vm.set_attr(&vm.sys_module, "meta_path", vm.ctx.new_list(vec![]))?;
+    }
+    if vm
+        .get_attribute(vm.sys_module.clone(), "path_hooks")
+        .is_err()
+    {
+        vm.set_attr(&vm.sys_module, "path_hooks", vm.ctx.new_list(vec![]))?;
+    }
+    if vm
+        .get_attribute(vm.sys_module.clone(), "path_importer_cache")
+        .is_err()
+    {
+        vm.set_attr(&vm.sys_module, "path_importer_cache", vm.ctx.new_dict())?;
+    }
     Ok(vm.get_none())
 }
 
 pub fn import_frozen(vm: &VirtualMachine, module_name: &str) -> PyResult {
-    if let Some(frozen) = vm.frozen.borrow().get(module_name) {
-        import_file(
-            vm,
-            module_name,
-            format!("frozen {}", module_name),
-            frozen.to_string(),
-        )
+    if let Some(bytes) = vm.frozen.borrow().get(module_name) {
+        import_frozen_code(vm, module_name, bytes)
     } else {
         Err(vm.new_import_error(format!("Cannot import frozen module {}", module_name)))
     }
 }
 
+// Entries that don't deserialize as a `CodeObject` are compiled as UTF-8
+// source instead, so a frozen module still stored as source text doesn't
+// take the VM down at startup.
+pub fn import_frozen_code(vm: &VirtualMachine, module_name: &str, bytes: &[u8]) -> PyResult {
+    let code_obj: CodeObject = match bincode::deserialize(bytes) {
+        Ok(code_obj) => code_obj,
+        Err(_) => {
+            let source = std::str::from_utf8(bytes).map_err(|e| {
+                vm.new_import_error(format!("Corrupt frozen module {}: {}", module_name, e))
+            })?;
+            compile::compile(
+                vm,
+                source,
+                &compile::Mode::Exec,
+                format!("frozen {}", module_name),
+            )
+            .map_err(|err| vm.new_syntax_error(&err))?
+        }
+    };
+    run_code_obj_as_module(vm, module_name, format!("frozen {}", module_name), code_obj)
+}
+
+pub fn register_frozen_module(vm: &VirtualMachine, module_name: String, code: Vec<u8>) {
+    vm.frozen.borrow_mut().insert(module_name, code);
+}
+
 pub fn import_builtin(vm: &VirtualMachine, module_name: &str) -> PyResult {
     let sys_modules = vm.get_attribute(vm.sys_module.clone(), "modules").unwrap();
     if let Some(make_module_func) = vm.stdlib_inits.borrow().get(module_name) {
@@ -48,32 +185,246 @@ pub fn import_builtin(vm: &VirtualMachine, module_name: &str) -> PyResult {
 }
 
 pub fn import_module(vm: &VirtualMachine, current_path: PathBuf, module_name: &str) -> PyResult {
+    if !log::log_enabled!(log::Level::Info) {
+        return import_module_inner(vm, current_path, module_name);
+    }
+    let start = Instant::now();
+    let result = import_module_inner(vm, current_path, module_name);
+    eprintln!(
+        "\x1b[36mimport\x1b[0m {:<30} \x1b[33m{:>8.3}ms\x1b[0m",
+        module_name,
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    result
+}
+
+fn import_module_inner(vm: &VirtualMachine, current_path: PathBuf, module_name: &str) -> PyResult {
     // Cached modules:
     let sys_modules = vm.get_attribute(vm.sys_module.clone(), "modules").unwrap();
 
     // First, see if we already loaded the module:
     if let Ok(module) = sys_modules.get_item(module_name.to_string(), vm) {
         Ok(module)
+    } else if let Some(module) = consult_meta_path(vm, module_name)? {
+        Ok(module)
     } else if vm.frozen.borrow().contains_key(module_name) {
         import_frozen(vm, module_name)
     } else if vm.stdlib_inits.borrow().contains_key(module_name) {
         import_builtin(vm, module_name)
     } else {
-        let notfound_error = vm.context().exceptions.module_not_found_error.clone();
-        let import_error = vm.context().exceptions.import_error.clone();
+        import_via_path(vm, current_path, module_name)
+    }
+}
+
+fn import_via_path(vm: &VirtualMachine, current_path: PathBuf, module_name: &str) -> PyResult {
+    let notfound_error = vm.context().exceptions.module_not_found_error.clone();
+    let import_error = vm.context().exceptions.import_error.clone();
+
+    let sys_path = vm.get_attribute(vm.sys_module.clone(), "path").unwrap();
+    let mut paths: Vec<PathBuf> = objsequence::get_elements_list(&sys_path)
+        .iter()
+        .map(|item| PathBuf::from(objstr::get_value(item)))
+        .collect();
+    paths.insert(0, current_path);
+
+    let rel_name = module_name.replace('.', "/");
+    let suffixes = [".py", "/__init__.py"];
+
+    for path in &paths {
+        let path_entry = path.to_string_lossy().into_owned();
+        if let Some(finder) = path_hook_finder(vm, &path_entry)? {
+            if let Some(module) = invoke_finder(vm, &finder, module_name)? {
+                return Ok(module);
+            }
+            continue;
+        }
+
+        if let Some(located) = locate_in_path_entry(path, &rel_name, &suffixes) {
+            let (display_path, source) = read_located_source(&located)
+                .map_err(|e| vm.new_exception(import_error.clone(), e))?;
+            return import_file(vm, module_name, display_path, source);
+        }
+    }
+
+    Err(vm.new_exception(
+        notfound_error,
+        format!("No module named '{}'", module_name),
+    ))
+}
 
-        // Time to search for module in any place:
-        let file_path = find_source(vm, current_path, module_name)
-            .map_err(|e| vm.new_exception(notfound_error.clone(), e))?;
-        let source = util::read_file(file_path.as_path())
-            .map_err(|e| vm.new_exception(import_error.clone(), e.to_string()))?;
+// Consulted before frozen/builtin/filesystem lookups, so Python code can
+// register import hooks on sys.meta_path.
+fn consult_meta_path(vm: &VirtualMachine, module_name: &str) -> PyResult<Option<PyObjectRef>> {
+    let meta_path = match vm.get_attribute(vm.sys_module.clone(), "meta_path") {
+        Ok(meta_path) => meta_path,
+        Err(_) => return Ok(None),
+    };
+    for finder in objsequence::get_elements_list(&meta_path).iter() {
+        if let Some(module) = invoke_finder(vm, finder, module_name)? {
+            return Ok(Some(module));
+        }
+    }
+    Ok(None)
+}
 
-        import_file(
-            vm,
-            module_name,
-            file_path.to_str().unwrap().to_string(),
-            source,
-        )
+// PEP 451 (find_spec -> spec.loader) with a PEP 302 (find_module -> loader)
+// fallback for finders that only implement the legacy protocol.
+fn invoke_finder(
+    vm: &VirtualMachine,
+    finder: &PyObjectRef,
+    module_name: &str,
+) -> PyResult<Option<PyObjectRef>> {
+    if let Ok(find_spec) = vm.get_attribute(finder.clone(), "find_spec") {
+        let spec = vm.invoke(
+            find_spec,
+            vec![vm.new_str(module_name.to_string()), vm.get_none()],
+        )?;
+        if !spec.is(&vm.get_none()) {
+            let loader = vm.get_attribute(spec.clone(), "loader")?;
+            return Ok(Some(load_via_loader(
+                vm,
+                &loader,
+                module_name,
+                Some(spec),
+            )?));
+        }
+    }
+
+    if let Ok(find_module) = vm.get_attribute(finder.clone(), "find_module") {
+        let loader = vm.invoke(find_module, vec![vm.new_str(module_name.to_string())])?;
+        if !loader.is(&vm.get_none()) {
+            return Ok(Some(load_via_loader(vm, &loader, module_name, None)?));
+        }
+    }
+
+    Ok(None)
+}
+
+// Prefers create_module/exec_module, falling back to the legacy
+// load_module single-call protocol.
+fn load_via_loader(
+    vm: &VirtualMachine,
+    loader: &PyObjectRef,
+    module_name: &str,
+    spec: Option<PyObjectRef>,
+) -> PyResult {
+    let sys_modules = vm.get_attribute(vm.sys_module.clone(), "modules").unwrap();
+
+    if let (Some(spec), Ok(create_module), Ok(exec_module)) = (
+        spec,
+        vm.get_attribute(loader.clone(), "create_module"),
+        vm.get_attribute(loader.clone(), "exec_module"),
+    ) {
+        let module = vm.invoke(create_module, vec![spec])?;
+        let module = if module.is(&vm.get_none()) {
+            let attrs = vm.ctx.new_dict();
+            attrs.set_item("__name__", vm.new_str(module_name.to_string()), vm)?;
+            vm.ctx.new_module(module_name, attrs)
+        } else {
+            module
+        };
+        sys_modules.set_item(module_name, module.clone(), vm)?;
+        vm.invoke(exec_module, vec![module.clone()])?;
+        return Ok(module);
+    }
+
+    let load_module = vm.get_attribute(loader.clone(), "load_module")?;
+    let module = vm.invoke(load_module, vec![vm.new_str(module_name.to_string())])?;
+    sys_modules.set_item(module_name, module.clone(), vm)?;
+    Ok(module)
+}
+
+// Mirrors CPython's PathFinder: consult sys.path_hooks, memoizing in
+// sys.path_importer_cache. None means no hook claims this entry.
+fn path_hook_finder(vm: &VirtualMachine, path_entry: &str) -> PyResult<Option<PyObjectRef>> {
+    let cache = match vm.get_attribute(vm.sys_module.clone(), "path_importer_cache") {
+        Ok(cache) => cache,
+        Err(_) => return Ok(None),
+    };
+    if let Ok(cached) = cache.get_item(path_entry.to_string(), vm) {
+        return Ok(if cached.is(&vm.get_none()) {
+            None
+        } else {
+            Some(cached)
+        });
+    }
+
+    let hooks = match vm.get_attribute(vm.sys_module.clone(), "path_hooks") {
+        Ok(hooks) => hooks,
+        Err(_) => return Ok(None),
+    };
+    for hook in objsequence::get_elements_list(&hooks).iter() {
+        if let Ok(finder) = vm.invoke(hook.clone(), vec![vm.new_str(path_entry.to_string())]) {
+            cache.set_item(path_entry.to_string(), finder.clone(), vm)?;
+            return Ok(Some(finder));
+        }
+    }
+    cache.set_item(path_entry.to_string(), vm.get_none(), vm)?;
+    Ok(None)
+}
+
+pub enum LocatedModule {
+    File(PathBuf),
+    Zip {
+        archive_path: PathBuf,
+        member: String,
+    },
+}
+
+thread_local! {
+    // Keeping the opened archive around means importing every module of a
+    // package from the same zip only pays the cost of opening it once.
+    static ZIP_ARCHIVES: RefCell<HashMap<PathBuf, zip::ZipArchive<fs::File>>> =
+        RefCell::new(HashMap::new());
+}
+
+// Splits mylib.zip/pkg into (mylib.zip, pkg).
+fn split_zip_prefix(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let mut archive_path = PathBuf::new();
+    for component in path.components() {
+        archive_path.push(component);
+        if archive_path.extension().map_or(false, |ext| ext == "zip") && archive_path.is_file() {
+            let remainder = path.strip_prefix(&archive_path).ok()?.to_path_buf();
+            return Some((archive_path, remainder));
+        }
+    }
+    None
+}
+
+fn zip_member_name(remainder: &Path, rel_name: &str, suffix: &str) -> String {
+    let mut member = remainder.to_string_lossy().replace('\\', "/");
+    if !member.is_empty() {
+        member.push('/');
+    }
+    member.push_str(rel_name);
+    member.push_str(suffix);
+    member
+}
+
+pub fn read_located_source(located: &LocatedModule) -> Result<(String, String), String> {
+    match located {
+        LocatedModule::File(path) => {
+            let source = util::read_file(path).map_err(|e| e.to_string())?;
+            Ok((path.to_str().unwrap().to_string(), source))
+        }
+        LocatedModule::Zip {
+            archive_path,
+            member,
+        } => ZIP_ARCHIVES.with(|archives| {
+            let mut archives = archives.borrow_mut();
+            if !archives.contains_key(archive_path) {
+                let file = fs::File::open(archive_path).map_err(|e| e.to_string())?;
+                let archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+                archives.insert(archive_path.clone(), archive);
+            }
+            let archive = archives.get_mut(archive_path).unwrap();
+            let mut entry = archive.by_name(member).map_err(|e| e.to_string())?;
+            let mut source = String::new();
+            entry.read_to_string(&mut source).map_err(|e| e.to_string())?;
+            // Keep tracebacks meaningful by pointing at the member inside the zip.
+            let display_path = format!("{}/{}", archive_path.display(), member);
+            Ok((display_path, source))
+        }),
     }
 }
 
@@ -82,11 +433,27 @@ pub fn import_file(
     module_name: &str,
     file_path: String,
     content: String,
+) -> PyResult {
+    let source_path = Path::new(&file_path);
+    let code_obj = if let Some(cached) = load_cached_code(source_path) {
+        cached
+    } else {
+        let code_obj = compile::compile(vm, &content, &compile::Mode::Exec, file_path.clone())
+            .map_err(|err| vm.new_syntax_error(&err))?;
+        // trace!("Code object: {:?}", code_obj);
+        store_cached_code(source_path, &code_obj);
+        code_obj
+    };
+    run_code_obj_as_module(vm, module_name, file_path, code_obj)
+}
+
+fn run_code_obj_as_module(
+    vm: &VirtualMachine,
+    module_name: &str,
+    file_path: String,
+    code_obj: CodeObject,
 ) -> PyResult {
     let sys_modules = vm.get_attribute(vm.sys_module.clone(), "modules").unwrap();
-    let code_obj = compile::compile(vm, &content, &compile::Mode::Exec, file_path.clone())
-        .map_err(|err| vm.new_syntax_error(&err))?;
-    // trace!("Code object: {:?}", code_obj);
 
     let attrs = vm.ctx.new_dict();
     attrs.set_item("__name__", vm.new_str(module_name.to_string()), vm)?;
@@ -104,7 +471,53 @@ pub fn import_file(
     Ok(module)
 }
 
-fn find_source(vm: &VirtualMachine, current_path: PathBuf, name: &str) -> Result<PathBuf, String> {
+// Shared by find_source and import_via_path's no-hook fallback.
+fn locate_in_path_entry(path: &Path, rel_name: &str, suffixes: &[&str]) -> Option<LocatedModule> {
+    if let Some((archive_path, remainder)) = split_zip_prefix(path) {
+        for suffix in suffixes {
+            let member = zip_member_name(&remainder, rel_name, suffix);
+            let found = ZIP_ARCHIVES.with(|archives| -> bool {
+                let mut archives = archives.borrow_mut();
+                if !archives.contains_key(&archive_path) {
+                    let file = match fs::File::open(&archive_path) {
+                        Ok(file) => file,
+                        Err(_) => return false,
+                    };
+                    let archive = match zip::ZipArchive::new(file) {
+                        Ok(archive) => archive,
+                        Err(_) => return false,
+                    };
+                    archives.insert(archive_path.clone(), archive);
+                }
+                archives
+                    .get_mut(&archive_path)
+                    .map_or(false, |archive| archive.by_name(&member).is_ok())
+            });
+            if found {
+                return Some(LocatedModule::Zip {
+                    archive_path,
+                    member,
+                });
+            }
+        }
+        return None;
+    }
+
+    for suffix in suffixes {
+        let mut file_path = path.to_path_buf();
+        file_path.push(format!("{}{}", rel_name, suffix));
+        if file_path.exists() {
+            return Some(LocatedModule::File(file_path));
+        }
+    }
+    None
+}
+
+pub fn find_source(
+    vm: &VirtualMachine,
+    current_path: PathBuf,
+    name: &str,
+) -> Result<LocatedModule, String> {
     let sys_path = vm.get_attribute(vm.sys_module.clone(), "path").unwrap();
     let mut paths: Vec<PathBuf> = objsequence::get_elements_list(&sys_path)
         .iter()
@@ -115,17 +528,186 @@ fn find_source(vm: &VirtualMachine, current_path: PathBuf, name: &str) -> Result
 
     let rel_name = name.replace('.', "/");
     let suffixes = [".py", "/__init__.py"];
-    let mut file_paths = vec![];
-    for path in paths {
-        for suffix in suffixes.iter() {
-            let mut file_path = path.clone();
-            file_path.push(format!("{}{}", rel_name, suffix));
-            file_paths.push(file_path);
+
+    for path in &paths {
+        if let Some(located) = locate_in_path_entry(path, &rel_name, &suffixes) {
+            return Ok(located);
         }
     }
 
-    match file_paths.iter().find(|p| p.exists()) {
-        Some(path) => Ok(path.to_path_buf()),
-        None => Err(format!("No module named '{}'", name)),
+    Err(format!("No module named '{}'", name))
+}
+
+#[test]
+fn test_import_frozen_code_roundtrip() {
+    let vm: VirtualMachine = Default::default();
+    let code_obj = compile::compile(
+        &vm,
+        "value = 42\n",
+        &compile::Mode::Exec,
+        "frozentest_mod".to_string(),
+    )
+    .unwrap();
+    let bytes = bincode::serialize(&code_obj).unwrap();
+
+    register_frozen_module(&vm, "frozentest_mod".to_string(), bytes);
+    let result = import_module(&vm, PathBuf::from("."), "frozentest_mod");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_import_frozen_code_legacy_source_fallback() {
+    // A frozen entry still holding raw source text (not yet converted to a
+    // serialized `CodeObject`) must still import rather than taking the VM
+    // down at startup.
+    let vm: VirtualMachine = Default::default();
+    register_frozen_module(
+        &vm,
+        "legacyfrozentest_mod".to_string(),
+        b"value = 1\n".to_vec(),
+    );
+    let result = import_module(&vm, PathBuf::from("."), "legacyfrozentest_mod");
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_import_module_timing_gate_does_not_panic() {
+    // Exercise both sides of `import_module`'s `log_enabled!` gate, not
+    // just the default (disabled) one. `log_enabled!` also asks the
+    // installed logger, so one that accepts Info has to be set.
+    struct InfoLogger;
+    impl log::Log for InfoLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Info
+        }
+        fn log(&self, _record: &log::Record) {}
+        fn flush(&self) {}
     }
+    static LOGGER: InfoLogger = InfoLogger;
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(log::LevelFilter::Info);
+    let vm: VirtualMachine = Default::default();
+    let code_obj = compile::compile(
+        &vm,
+        "value = 1\n",
+        &compile::Mode::Exec,
+        "timingtest_mod".to_string(),
+    )
+    .unwrap();
+    let bytes = bincode::serialize(&code_obj).unwrap();
+    register_frozen_module(&vm, "timingtest_mod".to_string(), bytes);
+
+    let result = import_module(&vm, PathBuf::from("."), "timingtest_mod");
+    log::set_max_level(log::LevelFilter::Off);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_pyc_cache_invalidation() {
+    use crate::obj::objint;
+
+    let vm: VirtualMachine = Default::default();
+    let dir = std::env::temp_dir().join(format!("rustpython_test_pyc_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source_path = dir.join("pyctest_mod.py");
+
+    fs::write(&source_path, "value = 1\n").unwrap();
+    let module = import_file(
+        &vm,
+        "pyctest_mod",
+        source_path.to_str().unwrap().to_string(),
+        fs::read_to_string(&source_path).unwrap(),
+    )
+    .unwrap();
+    let value = vm.get_attribute(module, "value").unwrap();
+    assert_eq!(objint::get_value(&value).to_i32(), Some(1));
+
+    let cache_path = cache_path_for(&source_path).unwrap();
+    assert!(cache_path.exists());
+
+    // Rewrite the source (same byte length, so only mtime distinguishes
+    // the two versions) with no delay, to prove sub-second edits aren't
+    // mistaken for a cache hit.
+    fs::write(&source_path, "value = 2\n").unwrap();
+    let module = import_file(
+        &vm,
+        "pyctest_mod",
+        source_path.to_str().unwrap().to_string(),
+        fs::read_to_string(&source_path).unwrap(),
+    )
+    .unwrap();
+    let value = vm.get_attribute(module, "value").unwrap();
+    assert_eq!(objint::get_value(&value).to_i32(), Some(2));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_import_module_from_zip() {
+    use std::io::Write;
+
+    let vm: VirtualMachine = Default::default();
+    let zip_path =
+        std::env::temp_dir().join(format!("rustpython_test_zipimport_{}.zip", std::process::id()));
+    {
+        let file = fs::File::create(&zip_path).unwrap();
+        let mut writer = zip::ZipWriter::new(file);
+        writer
+            .start_file("ziptest_mod.py", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(b"answer = 42\n").unwrap();
+        writer.finish().unwrap();
+    }
+
+    let sys_path = vm.get_attribute(vm.sys_module.clone(), "path").unwrap();
+    vm.call_method(
+        &sys_path,
+        "insert",
+        vec![
+            vm.new_int(0),
+            vm.new_str(zip_path.to_str().unwrap().to_string()),
+        ],
+    )
+    .unwrap();
+
+    let result = import_module(&vm, PathBuf::from("."), "ziptest_mod");
+    let _ = fs::remove_file(&zip_path);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_meta_path_finder_protocol() {
+    // A minimal PEP 451-style finder/loader pair registered on
+    // `sys.meta_path`; exercises `find_spec` -> `spec.loader` ->
+    // `create_module`/`exec_module`, not just the legacy `find_module` path.
+    let vm: VirtualMachine = Default::default();
+    let setup = r#"
+import sys
+
+class _Loader:
+    def create_module(self, spec):
+        return None
+    def exec_module(self, module):
+        module.answer = 42
+
+class _Spec:
+    def __init__(self):
+        self.loader = _Loader()
+
+class _Finder:
+    def find_spec(self, name, path):
+        if name == "metapathtest_mod":
+            return _Spec()
+        return None
+
+sys.meta_path.append(_Finder())
+"#;
+    let code = vm
+        .compile(setup, &compile::Mode::Exec, "<test>".to_string())
+        .unwrap();
+    vm.run_code_obj(code, Scope::with_builtins(None, vm.ctx.new_dict(), &vm))
+        .unwrap();
+
+    let result = import_module(&vm, PathBuf::from("."), "metapathtest_mod");
+    assert!(result.is_ok());
 }